@@ -2,9 +2,10 @@ use std::fmt::{self, Debug, Formatter};
 
 use super::{Content, Interruption, NodeId, Show, ShowNode, StyleEntry};
 use crate::diag::{At, TypResult};
-use crate::eval::{Args, Func, Value};
+use crate::eval::{Args, Dict, Func, Regex, Value};
 use crate::library::structure::{EnumNode, ListNode};
 use crate::syntax::Span;
+use crate::util::EcoString;
 use crate::Context;
 
 /// A show rule recipe.
@@ -21,10 +22,7 @@ pub struct Recipe {
 impl Recipe {
     /// Whether the recipe is applicable to the target.
     pub fn applicable(&self, target: Target) -> bool {
-        match (&self.pattern, target) {
-            (Pattern::Node(id), Target::Node(node)) => *id == node.id(),
-            _ => false,
-        }
+        applicable(&self.pattern, target)
     }
 
     /// Try to apply the recipe to the target.
@@ -34,21 +32,106 @@ impl Recipe {
         sel: Selector,
         target: Target,
     ) -> TypResult<Option<Content>> {
-        let content = match (target, &self.pattern) {
-            (Target::Node(node), &Pattern::Node(id)) if node.id() == id => {
-                let node = node.unguard(sel);
-                self.call(ctx, || {
-                    let dict = node.encode();
-                    Value::Content(Content::Show(node, Some(dict)))
-                })?
-            }
-
-            _ => return Ok(None),
+        let content = match self.apply_pattern(ctx, &self.pattern, sel, target)? {
+            Some(content) => content,
+            None => return Ok(None),
         };
 
         Ok(Some(content.styled_with_entry(StyleEntry::Guard(sel))))
     }
 
+    /// Try to apply a single (sub-)pattern to the target.
+    fn apply_pattern(
+        &self,
+        ctx: &mut Context,
+        pattern: &Pattern,
+        sel: Selector,
+        target: Target,
+    ) -> TypResult<Option<Content>> {
+        Ok(match (target, pattern) {
+            (Target::Node(node), Pattern::Node(id)) if node.id() == *id => {
+                Some(self.apply_node(ctx, sel, node, node.encode())?)
+            }
+
+            (Target::Node(node), Pattern::Label(label)) if has_label(node, label) => {
+                Some(self.apply_node(ctx, sel, node, node.encode())?)
+            }
+
+            (Target::Node(node), Pattern::Where(id, fields)) if node.id() == *id => {
+                let dict = node.encode();
+                if has_fields(&dict, fields) {
+                    Some(self.apply_node(ctx, sel, node, dict)?)
+                } else {
+                    None
+                }
+            }
+
+            (Target::Text(text), Pattern::Text(regex)) if regex.is_match(text) => {
+                Some(self.apply_text(ctx, regex, text)?)
+            }
+
+            (target, Pattern::Any(patterns)) => {
+                let mut result = None;
+                for sub in patterns {
+                    if applicable(sub, target) {
+                        result = self.apply_pattern(ctx, sub, sel, target)?;
+                        break;
+                    }
+                }
+                result
+            }
+
+            (target, Pattern::All(patterns)) => {
+                if patterns.iter().all(|sub| applicable(sub, target)) {
+                    match patterns.first() {
+                        Some(sub) => self.apply_pattern(ctx, sub, sel, target)?,
+                        None => None,
+                    }
+                } else {
+                    None
+                }
+            }
+
+            _ => None,
+        })
+    }
+
+    /// Apply the recipe to a whole node, passing along its already-encoded
+    /// fields as the call argument.
+    fn apply_node(
+        &self,
+        ctx: &mut Context,
+        sel: Selector,
+        node: &ShowNode,
+        dict: Dict,
+    ) -> TypResult<Content> {
+        let node = node.unguard(sel);
+        self.call(ctx, || Value::Content(Content::Show(node, Some(dict))))
+    }
+
+    /// Apply the recipe to each match of the regex within a run of text,
+    /// splicing the recipe's results back in between the unmatched pieces.
+    fn apply_text(&self, ctx: &mut Context, regex: &Regex, text: &str) -> TypResult<Content> {
+        let mut result = vec![];
+        let mut cursor = 0;
+
+        for mat in regex.find_iter(text) {
+            if mat.start() > cursor {
+                result.push(Content::Text(text[cursor .. mat.start()].into()));
+            }
+
+            let piece = mat.as_str();
+            result.push(self.call(ctx, || Value::Str(piece.into()))?);
+            cursor = mat.end();
+        }
+
+        if cursor < text.len() {
+            result.push(Content::Text(text[cursor ..].into()));
+        }
+
+        Ok(Content::sequence(result))
+    }
+
     /// Call the recipe function, with the argument if desired.
     fn call<F>(&self, ctx: &mut Context, arg: F) -> TypResult<Content>
     where
@@ -65,10 +148,8 @@ impl Recipe {
 
     /// What kind of structure the property interrupts.
     pub fn interruption(&self) -> Option<Interruption> {
-        if let Pattern::Node(id) = self.pattern {
-            if id == NodeId::of::<ListNode>() || id == NodeId::of::<EnumNode>() {
-                return Some(Interruption::List);
-            }
+        if interrupts_list(&self.pattern) {
+            return Some(Interruption::List);
         }
 
         None
@@ -81,11 +162,66 @@ impl Debug for Recipe {
     }
 }
 
+/// Whether a pattern matches a target, recursing into combinators.
+fn applicable(pattern: &Pattern, target: Target) -> bool {
+    match (pattern, target) {
+        (Pattern::Node(id), Target::Node(node)) => *id == node.id(),
+        (Pattern::Text(regex), Target::Text(text)) => regex.is_match(text),
+        (Pattern::Label(label), Target::Node(node)) => has_label(node, label),
+        (Pattern::Where(id, fields), Target::Node(node)) => {
+            *id == node.id() && has_fields(&node.encode(), fields)
+        }
+        (Pattern::Any(patterns), target) => patterns.iter().any(|p| applicable(p, target)),
+        (Pattern::All(patterns), target) => {
+            !patterns.is_empty() && patterns.iter().all(|p| applicable(p, target))
+        }
+        _ => false,
+    }
+}
+
+/// Whether a pattern resolves to the list or enum node id, and thus
+/// interrupts list building wherever it may match.
+fn interrupts_list(pattern: &Pattern) -> bool {
+    match pattern {
+        Pattern::Node(id) | Pattern::Where(id, _) => {
+            *id == NodeId::of::<ListNode>() || *id == NodeId::of::<EnumNode>()
+        }
+        Pattern::Any(patterns) | Pattern::All(patterns) => {
+            patterns.iter().any(interrupts_list)
+        }
+        Pattern::Text(_) | Pattern::Label(_) => false,
+    }
+}
+
+/// Whether a node carries the given label.
+fn has_label(node: &ShowNode, label: &EcoString) -> bool {
+    node.label().map_or(false, |candidate| candidate == label)
+}
+
+/// Whether an encoded field dict contains each of the given name-value
+/// pairs.
+fn has_fields(dict: &Dict, fields: &[(EcoString, Value)]) -> bool {
+    fields
+        .iter()
+        .all(|(name, value)| dict.iter().any(|(key, v)| key == name.as_str() && v == value))
+}
+
 /// A show rule pattern that may match a target.
 #[derive(Debug, Clone, PartialEq, Hash)]
 pub enum Pattern {
     /// Defines the appearence of some node.
     Node(NodeId),
+    /// Defines the appearance of text matching a regular expression.
+    Text(Regex),
+    /// Defines the appearance of nodes carrying a specific label.
+    Label(EcoString),
+    /// Defines the appearance of some node, constrained to those whose
+    /// encoded fields match the given values.
+    Where(NodeId, Vec<(EcoString, Value)>),
+    /// Matches if any of the sub-patterns match.
+    Any(Vec<Pattern>),
+    /// Matches if all of the sub-patterns match.
+    All(Vec<Pattern>),
 }
 
 /// A target for a show rule recipe.
@@ -93,6 +229,8 @@ pub enum Pattern {
 pub enum Target<'a> {
     /// A showable node.
     Node(&'a ShowNode),
+    /// A run of text.
+    Text(&'a str),
 }
 
 /// Identifies a show rule recipe.
@@ -102,4 +240,4 @@ pub enum Selector {
     Nth(usize),
     /// The base recipe for a kind of node.
     Base(NodeId),
-}
\ No newline at end of file
+}